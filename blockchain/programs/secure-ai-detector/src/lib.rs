@@ -1,45 +1,699 @@
 use anchor_lang::prelude::*;
+use anchor_lang::solana_program::{ed25519_program, sysvar::instructions as sysvar_instructions};
+use sha2::{Digest, Sha256};
 
 declare_id!("YourProgramIdHere");  // Replace with your actual program ID after building
 
+/// Seed used for every per-video PDA, paired with the sha256 of `video_hash`.
+pub const VIDEO_SEED_PREFIX: &[u8] = b"video";
+
+/// Hashes the raw `video_hash` string down to a fixed 32-byte PDA seed.
+///
+/// `video_hash` is attacker/uploader supplied and may exceed Solana's 32-byte
+/// seed limit, so it can't be used as a seed directly.
+pub fn hash_video_hash(video_hash: &str) -> [u8; 32] {
+    Sha256::digest(video_hash.as_bytes()).into()
+}
+
+/// Off-chain clients can call this (or replicate it) to compute the
+/// deterministic storage address for a given video without needing an
+/// external index.
+pub fn find_storage_address(video_hash: &str) -> (Pubkey, u8) {
+    let hashed_seed = hash_video_hash(video_hash);
+    Pubkey::find_program_address(&[VIDEO_SEED_PREFIX, hashed_seed.as_ref()], &crate::ID)
+}
+
+/// The message an attestor must sign off-chain before a score is trusted
+/// on-chain: `sha256(video_hash || authenticity_score.to_le_bytes())`.
+pub fn attestation_message(video_hash: &str, authenticity_score: u64) -> [u8; 32] {
+    let mut hasher = Sha256::new();
+    hasher.update(video_hash.as_bytes());
+    hasher.update(authenticity_score.to_le_bytes());
+    hasher.finalize().into()
+}
+
+/// Seed for the single global config account listing allowlisted attestors.
+pub const ATTESTOR_CONFIG_SEED: &[u8] = b"attestor-config";
+
+/// Maximum number of trusted attestor pubkeys the config account can hold.
+pub const MAX_ATTESTORS: usize = 16;
+
+/// Number of past score revisions kept on-chain; older entries are
+/// overwritten in ring-buffer order once this is exceeded.
+pub const MAX_SCORE_HISTORY: usize = 32;
+
+/// One recorded authenticity judgement, kept around so the full revision
+/// history is itself forensic evidence rather than being overwritten.
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, Default)]
+pub struct ScoreEntry {
+    pub score: u64,
+    pub slot: u64,
+    pub updater: Pubkey,
+}
+
+/// Maximum number of independent detector models whose scores can be
+/// collected for a single video before it must be finalized.
+pub const MAX_DETECTORS: usize = 16;
+
+/// A single detector model's contribution to the consensus verdict.
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, Default)]
+pub struct DetectorScore {
+    pub detector_id: Pubkey,
+    pub score: u64,
+}
+
+/// Seed for the single global config account listing allowlisted detectors.
+pub const DETECTOR_CONFIG_SEED: &[u8] = b"detector-config";
+
+/// Maximum number of registered detector pubkeys the config account can hold.
+pub const MAX_REGISTERED_DETECTORS: usize = 32;
+
+/// Computes the median of the collected detector scores.
+///
+/// Averages the two middle values with a `u128` widening add so that two
+/// detectors each submitting a score near `u64::MAX` can't overflow the sum.
+fn median_score(scores: &[u64]) -> u64 {
+    let mut sorted = scores.to_vec();
+    sorted.sort_unstable();
+    let mid = sorted.len() / 2;
+    if sorted.len() % 2 == 0 {
+        ((sorted[mid - 1] as u128 + sorted[mid] as u128) / 2) as u64
+    } else {
+        sorted[mid]
+    }
+}
+
+/// `u16::MAX` is the Ed25519Program's convention for "this instruction".
+const CURRENT_INSTRUCTION: u16 = u16::MAX;
+
+/// Validates the raw data of a native Ed25519 SigVerify instruction against
+/// an expected attestor/signature/message, without trusting the fixed
+/// offsets until the offsets table itself has been checked.
+///
+/// Layout of a single-signature Ed25519Program instruction: a 2-byte header
+/// (num_signatures, padding), a 14-byte Ed25519SignatureOffsets table, then
+/// the public key, signature and message at whatever offsets that table
+/// designates. The native program only verifies the bytes its own offsets
+/// point at, so we must assert those offsets land exactly on our expected
+/// layout (and reference this same instruction) before trusting the
+/// fixed-offset reads below — otherwise an attacker could point the real
+/// crypto check at a throwaway keypair/message elsewhere in the data while
+/// placing unchecked bytes at the positions we read.
+fn verify_ed25519_ix_data(
+    data: &[u8],
+    attestor: &Pubkey,
+    signature: &[u8; 64],
+    message: &[u8],
+) -> Result<()> {
+    require!(data.len() >= 16, ErrorCode::InvalidAttestation);
+    require!(data[0] == 1, ErrorCode::InvalidAttestation);
+
+    let read_u16 = |offset: usize| -> u16 { u16::from_le_bytes([data[offset], data[offset + 1]]) };
+    let signature_offset = read_u16(2) as usize;
+    let signature_instruction_index = read_u16(4);
+    let public_key_offset = read_u16(6) as usize;
+    let public_key_instruction_index = read_u16(8);
+    let message_data_offset = read_u16(10) as usize;
+    let message_data_size = read_u16(12) as usize;
+    let message_instruction_index = read_u16(14);
+
+    require!(
+        signature_instruction_index == CURRENT_INSTRUCTION
+            && public_key_instruction_index == CURRENT_INSTRUCTION
+            && message_instruction_index == CURRENT_INSTRUCTION,
+        ErrorCode::InvalidAttestation
+    );
+    require!(public_key_offset == 16, ErrorCode::InvalidAttestation);
+    require!(signature_offset == 48, ErrorCode::InvalidAttestation);
+    require!(message_data_offset == 112, ErrorCode::InvalidAttestation);
+    require!(message_data_size == message.len(), ErrorCode::InvalidAttestation);
+    require!(
+        data.len() >= message_data_offset + message_data_size,
+        ErrorCode::InvalidAttestation
+    );
+
+    require!(
+        &data[public_key_offset..public_key_offset + 32] == attestor.as_ref(),
+        ErrorCode::InvalidAttestation
+    );
+    require!(
+        &data[signature_offset..signature_offset + 64] == signature.as_ref(),
+        ErrorCode::InvalidAttestation
+    );
+    require!(
+        &data[message_data_offset..message_data_offset + message_data_size] == message,
+        ErrorCode::InvalidAttestation
+    );
+
+    Ok(())
+}
+
+/// Confirms the instruction immediately preceding this one in the same
+/// transaction is a native Ed25519 SigVerify instruction attesting that
+/// `attestor` signed `message`, as produced by
+/// `solana_program::ed25519_instruction::new_ed25519_instruction`.
+fn verify_attestation(
+    ix_sysvar: &AccountInfo,
+    attestor: &Pubkey,
+    signature: &[u8; 64],
+    message: &[u8],
+) -> Result<()> {
+    let current_index = sysvar_instructions::load_current_index_checked(ix_sysvar)?;
+    require!(current_index > 0, ErrorCode::MissingAttestation);
+
+    let ed25519_ix =
+        sysvar_instructions::load_instruction_at_checked((current_index - 1) as usize, ix_sysvar)?;
+    require_keys_eq!(
+        ed25519_ix.program_id,
+        ed25519_program::ID,
+        ErrorCode::MissingAttestation
+    );
+
+    verify_ed25519_ix_data(&ed25519_ix.data, attestor, signature, message)
+}
+
 #[program]
 pub mod secure_ai_detector {
     use super::*;
 
-    pub fn initialize(ctx: Context<Initialize>, video_hash: String, authenticity_score: u64) -> Result<()> {
+    /// Creates the single global allowlist of trusted attestor pubkeys.
+    /// Only `admin` may add or remove entries afterwards.
+    pub fn initialize_config(ctx: Context<InitializeConfig>) -> Result<()> {
+        let config = &mut ctx.accounts.config;
+        config.admin = ctx.accounts.admin.key();
+        config.bump = ctx.bumps.config;
+        config.attestors = Vec::new();
+        Ok(())
+    }
+
+    pub fn add_attestor(ctx: Context<ModifyConfig>, attestor: Pubkey) -> Result<()> {
+        let config = &mut ctx.accounts.config;
+        require!(
+            !config.attestors.contains(&attestor),
+            ErrorCode::AttestorAlreadyAllowlisted
+        );
+        require!(
+            config.attestors.len() < MAX_ATTESTORS,
+            ErrorCode::AttestorCapacityExceeded
+        );
+        config.attestors.push(attestor);
+        Ok(())
+    }
+
+    pub fn remove_attestor(ctx: Context<ModifyConfig>, attestor: Pubkey) -> Result<()> {
+        let config = &mut ctx.accounts.config;
+        let original_len = config.attestors.len();
+        config.attestors.retain(|candidate| candidate != &attestor);
+        require!(
+            config.attestors.len() < original_len,
+            ErrorCode::AttestorNotAllowlisted
+        );
+        Ok(())
+    }
+
+    /// Creates the single global allowlist of registered detector models.
+    /// Only `admin` may add or remove entries afterwards.
+    pub fn initialize_detector_config(ctx: Context<InitializeDetectorConfig>) -> Result<()> {
+        let config = &mut ctx.accounts.config;
+        config.admin = ctx.accounts.admin.key();
+        config.bump = ctx.bumps.config;
+        config.detectors = Vec::new();
+        Ok(())
+    }
+
+    pub fn add_detector(ctx: Context<ModifyDetectorConfig>, detector_id: Pubkey) -> Result<()> {
+        let config = &mut ctx.accounts.config;
+        require!(
+            !config.detectors.contains(&detector_id),
+            ErrorCode::DetectorAlreadyRegistered
+        );
+        require!(
+            config.detectors.len() < MAX_REGISTERED_DETECTORS,
+            ErrorCode::DetectorRegistryFull
+        );
+        config.detectors.push(detector_id);
+        Ok(())
+    }
+
+    pub fn remove_detector(ctx: Context<ModifyDetectorConfig>, detector_id: Pubkey) -> Result<()> {
+        let config = &mut ctx.accounts.config;
+        let original_len = config.detectors.len();
+        config.detectors.retain(|candidate| candidate != &detector_id);
+        require!(
+            config.detectors.len() < original_len,
+            ErrorCode::DetectorNotRegistered
+        );
+        Ok(())
+    }
+
+    pub fn initialize(
+        ctx: Context<Initialize>,
+        video_hash: String,
+        authenticity_score: u64,
+        signature: [u8; 64],
+        attestor: Pubkey,
+        quorum: u8,
+    ) -> Result<()> {
+        require!(
+            quorum >= 1 && (quorum as usize) <= MAX_DETECTORS,
+            ErrorCode::InvalidQuorum
+        );
+        require!(
+            ctx.accounts.config.attestors.contains(&attestor),
+            ErrorCode::AttestorNotAllowlisted
+        );
+        let message = attestation_message(&video_hash, authenticity_score);
+        verify_attestation(
+            &ctx.accounts.instructions_sysvar,
+            &attestor,
+            &signature,
+            &message,
+        )?;
+
+        let updater = ctx.accounts.signer.key();
         let storage_account = &mut ctx.accounts.storage_account;
-        storage_account.video_hash = video_hash;
-        storage_account.authenticity_score = authenticity_score;
+        storage_account.authority = updater;
+        storage_account.bump = ctx.bumps.storage_account;
+        storage_account.attestor = attestor;
+        storage_account.video_hash = video_hash.clone();
+        storage_account.push_revision(authenticity_score, Clock::get()?.slot, updater);
+        storage_account.quorum = quorum;
         msg!("Stored video hash: {} and authenticity score: {}!", video_hash, authenticity_score);
         Ok(())
     }
 
-    pub fn update(ctx: Context<Update>, new_video_hash: String, new_authenticity_score: u64) -> Result<()> {
+    pub fn update(
+        ctx: Context<Update>,
+        new_authenticity_score: u64,
+        signature: [u8; 64],
+        attestor: Pubkey,
+    ) -> Result<()> {
+        require!(
+            !ctx.accounts.storage_account.finalized,
+            ErrorCode::AlreadyFinalized
+        );
+        require!(
+            ctx.accounts.config.attestors.contains(&attestor),
+            ErrorCode::AttestorNotAllowlisted
+        );
+        let video_hash = ctx.accounts.storage_account.video_hash.clone();
+        let message = attestation_message(&video_hash, new_authenticity_score);
+        verify_attestation(
+            &ctx.accounts.instructions_sysvar,
+            &attestor,
+            &signature,
+            &message,
+        )?;
+
+        let updater = ctx.accounts.authority.key();
+        let storage_account = &mut ctx.accounts.storage_account;
+        storage_account.attestor = attestor;
+        storage_account.push_revision(new_authenticity_score, Clock::get()?.slot, updater);
+        msg!("Updated authenticity score for video {}: {}!", video_hash, new_authenticity_score);
+        Ok(())
+    }
+
+    pub fn submit_score(ctx: Context<SubmitScore>, detector_id: Pubkey, score: u64) -> Result<()> {
+        require_keys_eq!(
+            detector_id,
+            ctx.accounts.detector.key(),
+            ErrorCode::DetectorMismatch
+        );
+        require!(
+            ctx.accounts.config.detectors.contains(&detector_id),
+            ErrorCode::DetectorNotRegistered
+        );
+
         let storage_account = &mut ctx.accounts.storage_account;
-        storage_account.video_hash = new_video_hash;
-        storage_account.authenticity_score = new_authenticity_score;
-        msg!("Updated video hash: {} and authenticity score: {}!", new_video_hash, new_authenticity_score);
+        require!(!storage_account.finalized, ErrorCode::AlreadyFinalized);
+        require!(
+            !storage_account.detector_scores[..storage_account.detector_count as usize]
+                .iter()
+                .any(|entry| entry.detector_id == detector_id),
+            ErrorCode::DuplicateDetector
+        );
+        require!(
+            (storage_account.detector_count as usize) < MAX_DETECTORS,
+            ErrorCode::DetectorCapacityExceeded
+        );
+
+        let index = storage_account.detector_count as usize;
+        storage_account.detector_scores[index] = DetectorScore { detector_id, score };
+        storage_account.detector_count += 1;
+        msg!("Recorded score {} from detector {}", score, detector_id);
+        Ok(())
+    }
+
+    pub fn finalize(ctx: Context<Finalize>) -> Result<()> {
+        let storage_account = &mut ctx.accounts.storage_account;
+        require!(!storage_account.finalized, ErrorCode::AlreadyFinalized);
+        require!(storage_account.detector_count > 0, ErrorCode::QuorumNotMet);
+        require!(
+            storage_account.detector_count >= storage_account.quorum,
+            ErrorCode::QuorumNotMet
+        );
+
+        let scores: Vec<u64> = storage_account.detector_scores
+            [..storage_account.detector_count as usize]
+            .iter()
+            .map(|entry| entry.score)
+            .collect();
+        let final_score = median_score(&scores);
+
+        storage_account.final_score = final_score;
+        storage_account.finalized = true;
+
+        emit!(ScoreFinalized {
+            video_hash: storage_account.video_hash.clone(),
+            final_score,
+            detector_count: storage_account.detector_count,
+        });
         Ok(())
     }
 }
 
 #[derive(Accounts)]
+#[instruction(video_hash: String)]
 pub struct Initialize<'info> {
-    #[account(init, payer = signer, space = 8 + 4 + 256 + 8)]
+    #[account(
+        init,
+        payer = signer,
+        space = 8 + 32 + 1 + 32 + 4 + 256 + (48 * MAX_SCORE_HISTORY) + 8
+            + (40 * MAX_DETECTORS) + 1 + 1 + 1 + 8,
+        seeds = [VIDEO_SEED_PREFIX, hash_video_hash(&video_hash).as_ref()],
+        bump,
+    )]
     pub storage_account: Account<'info, StorageAccount>,
     #[account(mut)]
     pub signer: Signer<'info>,
     pub system_program: Program<'info, System>,
+    /// CHECK: validated by address constraint against the sysvar id.
+    #[account(address = sysvar_instructions::ID)]
+    pub instructions_sysvar: AccountInfo<'info>,
+    #[account(seeds = [ATTESTOR_CONFIG_SEED], bump = config.bump)]
+    pub config: Account<'info, AttestorConfig>,
 }
 
 #[derive(Accounts)]
 pub struct Update<'info> {
+    #[account(mut, has_one = authority @ ErrorCode::Unauthorized)]
+    pub storage_account: Account<'info, StorageAccount>,
+    pub authority: Signer<'info>,
+    /// CHECK: validated by address constraint against the sysvar id.
+    #[account(address = sysvar_instructions::ID)]
+    pub instructions_sysvar: AccountInfo<'info>,
+    #[account(seeds = [ATTESTOR_CONFIG_SEED], bump = config.bump)]
+    pub config: Account<'info, AttestorConfig>,
+}
+
+#[derive(Accounts)]
+pub struct InitializeConfig<'info> {
+    #[account(
+        init,
+        payer = admin,
+        space = 8 + 32 + 1 + 4 + 32 * MAX_ATTESTORS,
+        seeds = [ATTESTOR_CONFIG_SEED],
+        bump,
+    )]
+    pub config: Account<'info, AttestorConfig>,
     #[account(mut)]
+    pub admin: Signer<'info>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct ModifyConfig<'info> {
+    #[account(
+        mut,
+        seeds = [ATTESTOR_CONFIG_SEED],
+        bump = config.bump,
+        has_one = admin @ ErrorCode::Unauthorized,
+    )]
+    pub config: Account<'info, AttestorConfig>,
+    pub admin: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct SubmitScore<'info> {
+    #[account(mut)]
+    pub storage_account: Account<'info, StorageAccount>,
+    pub detector: Signer<'info>,
+    #[account(seeds = [DETECTOR_CONFIG_SEED], bump = config.bump)]
+    pub config: Account<'info, DetectorConfig>,
+}
+
+#[derive(Accounts)]
+pub struct InitializeDetectorConfig<'info> {
+    #[account(
+        init,
+        payer = admin,
+        space = 8 + 32 + 1 + 4 + 32 * MAX_REGISTERED_DETECTORS,
+        seeds = [DETECTOR_CONFIG_SEED],
+        bump,
+    )]
+    pub config: Account<'info, DetectorConfig>,
+    #[account(mut)]
+    pub admin: Signer<'info>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct ModifyDetectorConfig<'info> {
+    #[account(
+        mut,
+        seeds = [DETECTOR_CONFIG_SEED],
+        bump = config.bump,
+        has_one = admin @ ErrorCode::Unauthorized,
+    )]
+    pub config: Account<'info, DetectorConfig>,
+    pub admin: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct Finalize<'info> {
+    #[account(mut, has_one = authority @ ErrorCode::Unauthorized)]
     pub storage_account: Account<'info, StorageAccount>,
+    pub authority: Signer<'info>,
+}
+
+/// Global allowlist of attestor pubkeys trusted to sign authenticity scores.
+#[account]
+pub struct AttestorConfig {
+    pub admin: Pubkey,
+    pub bump: u8,
+    pub attestors: Vec<Pubkey>,
 }
 
+/// Global allowlist of detector pubkeys registered as legitimate ensemble
+/// members; without this, `submit_score` would accept a score from any
+/// freshly generated keypair, making the quorum gate in `finalize` cosmetic.
 #[account]
+pub struct DetectorConfig {
+    pub admin: Pubkey,
+    pub bump: u8,
+    pub detectors: Vec<Pubkey>,
+}
+
+#[account]
+#[derive(Default)]
 pub struct StorageAccount {
+    pub authority: Pubkey,
+    pub bump: u8,
+    pub attestor: Pubkey,
     pub video_hash: String,
-    pub authenticity_score: u64,
-}
\ No newline at end of file
+    /// Ring buffer of the last `MAX_SCORE_HISTORY` score revisions, indexed
+    /// by `revision_count % MAX_SCORE_HISTORY`.
+    pub revisions: [ScoreEntry; MAX_SCORE_HISTORY],
+    /// Total number of revisions ever recorded, including ones already
+    /// overwritten in the ring buffer.
+    pub revision_count: u64,
+    /// Scores submitted so far by independent detector models.
+    pub detector_scores: [DetectorScore; MAX_DETECTORS],
+    /// Number of entries populated in `detector_scores`.
+    pub detector_count: u8,
+    /// Minimum number of distinct detectors required before `finalize` is
+    /// allowed to run.
+    pub quorum: u8,
+    /// Once true, `submit_score` is rejected and `final_score` is settled.
+    pub finalized: bool,
+    /// The consensus score computed by `finalize`, valid once `finalized`.
+    pub final_score: u64,
+}
+
+impl StorageAccount {
+    /// Records a new score revision, overwriting the oldest entry once the
+    /// ring buffer is full.
+    pub fn push_revision(&mut self, score: u64, slot: u64, updater: Pubkey) {
+        let index = (self.revision_count as usize) % MAX_SCORE_HISTORY;
+        self.revisions[index] = ScoreEntry { score, slot, updater };
+        self.revision_count += 1;
+    }
+
+    /// Returns the most recently recorded score revision, or `None` if no
+    /// revision has been pushed yet.
+    pub fn current_score(&self) -> Option<ScoreEntry> {
+        if self.revision_count == 0 {
+            return None;
+        }
+        let index = ((self.revision_count - 1) as usize) % MAX_SCORE_HISTORY;
+        Some(self.revisions[index])
+    }
+}
+
+#[event]
+pub struct ScoreFinalized {
+    pub video_hash: String,
+    pub final_score: u64,
+    pub detector_count: u8,
+}
+
+#[error_code]
+pub enum ErrorCode {
+    #[msg("The signer is not the authority or admin permitted to modify this account.")]
+    Unauthorized,
+    #[msg("An Ed25519 attestation instruction must precede this instruction.")]
+    MissingAttestation,
+    #[msg("The attestation signature does not match the attestor and score.")]
+    InvalidAttestation,
+    #[msg("The attestor is not on the trusted allowlist.")]
+    AttestorNotAllowlisted,
+    #[msg("This attestor is already on the allowlist.")]
+    AttestorAlreadyAllowlisted,
+    #[msg("The attestor allowlist is full.")]
+    AttestorCapacityExceeded,
+    #[msg("The detector_id argument does not match the submitting signer.")]
+    DetectorMismatch,
+    #[msg("This detector has already submitted a score for this video.")]
+    DuplicateDetector,
+    #[msg("The maximum number of detector submissions has been reached.")]
+    DetectorCapacityExceeded,
+    #[msg("This detector is not registered on the trusted allowlist.")]
+    DetectorNotRegistered,
+    #[msg("This detector is already registered.")]
+    DetectorAlreadyRegistered,
+    #[msg("The detector registry is full.")]
+    DetectorRegistryFull,
+    #[msg("This record has already been finalized.")]
+    AlreadyFinalized,
+    #[msg("Not enough distinct detectors have submitted scores to meet quorum.")]
+    QuorumNotMet,
+    #[msg("quorum must be between 1 and the maximum number of detectors.")]
+    InvalidQuorum,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Builds the bytes of a single-signature Ed25519Program instruction
+    /// with an offsets table pointing at our canonical layout, as produced
+    /// by `solana_program::ed25519_instruction::new_ed25519_instruction`.
+    fn build_ed25519_ix_data(public_key: &[u8; 32], signature: &[u8; 64], message: &[u8]) -> Vec<u8> {
+        let mut data = vec![0u8; 112];
+        data[0] = 1; // num_signatures
+        data[1] = 0; // padding
+        data[2..4].copy_from_slice(&(48u16).to_le_bytes()); // signature_offset
+        data[4..6].copy_from_slice(&CURRENT_INSTRUCTION.to_le_bytes());
+        data[6..8].copy_from_slice(&(16u16).to_le_bytes()); // public_key_offset
+        data[8..10].copy_from_slice(&CURRENT_INSTRUCTION.to_le_bytes());
+        data[10..12].copy_from_slice(&(112u16).to_le_bytes()); // message_data_offset
+        data[12..14].copy_from_slice(&(message.len() as u16).to_le_bytes());
+        data[14..16].copy_from_slice(&CURRENT_INSTRUCTION.to_le_bytes());
+        data[16..48].copy_from_slice(public_key);
+        data[48..112].copy_from_slice(signature);
+        data.extend_from_slice(message);
+        data
+    }
+
+    #[test]
+    fn verify_ed25519_ix_data_accepts_well_formed_instruction() {
+        let attestor = Pubkey::new_from_array([7u8; 32]);
+        let signature = [9u8; 64];
+        let message = b"sha256(video_hash || score)".to_vec();
+        let data = build_ed25519_ix_data(&attestor.to_bytes(), &signature, &message);
+
+        assert!(verify_ed25519_ix_data(&data, &attestor, &signature, &message).is_ok());
+    }
+
+    #[test]
+    fn verify_ed25519_ix_data_rejects_offsets_pointing_elsewhere() {
+        let attestor = Pubkey::new_from_array([7u8; 32]);
+        let signature = [9u8; 64];
+        let message = b"sha256(video_hash || score)".to_vec();
+        let mut data = build_ed25519_ix_data(&attestor.to_bytes(), &signature, &message);
+
+        // An attacker rewrites the offsets table so the real crypto check
+        // targets a throwaway key/message placed elsewhere in the data,
+        // while the bytes at the fixed [16..112+] range still equal what
+        // we expect. This must be rejected even though the fixed-offset
+        // bytes "match".
+        data[6..8].copy_from_slice(&(200u16).to_le_bytes()); // public_key_offset moved
+
+        assert!(verify_ed25519_ix_data(&data, &attestor, &signature, &message).is_err());
+    }
+
+    #[test]
+    fn verify_ed25519_ix_data_rejects_instruction_index_not_self() {
+        let attestor = Pubkey::new_from_array([7u8; 32]);
+        let signature = [9u8; 64];
+        let message = b"sha256(video_hash || score)".to_vec();
+        let mut data = build_ed25519_ix_data(&attestor.to_bytes(), &signature, &message);
+        data[8..10].copy_from_slice(&0u16.to_le_bytes()); // public_key_instruction_index
+
+        assert!(verify_ed25519_ix_data(&data, &attestor, &signature, &message).is_err());
+    }
+
+    #[test]
+    fn verify_ed25519_ix_data_rejects_wrong_attestor() {
+        let attestor = Pubkey::new_from_array([7u8; 32]);
+        let other = Pubkey::new_from_array([8u8; 32]);
+        let signature = [9u8; 64];
+        let message = b"sha256(video_hash || score)".to_vec();
+        let data = build_ed25519_ix_data(&attestor.to_bytes(), &signature, &message);
+
+        assert!(verify_ed25519_ix_data(&data, &other, &signature, &message).is_err());
+    }
+
+    #[test]
+    fn verify_ed25519_ix_data_rejects_truncated_data() {
+        let attestor = Pubkey::new_from_array([7u8; 32]);
+        let signature = [9u8; 64];
+        let message = b"m".to_vec();
+        let data = vec![0u8; 10];
+
+        assert!(verify_ed25519_ix_data(&data, &attestor, &signature, &message).is_err());
+    }
+
+    #[test]
+    fn median_score_odd_count_returns_middle_value() {
+        assert_eq!(median_score(&[30, 10, 20]), 20);
+    }
+
+    #[test]
+    fn median_score_even_count_averages_middle_two() {
+        assert_eq!(median_score(&[10, 20, 30, 40]), 25);
+    }
+
+    #[test]
+    fn median_score_does_not_overflow_on_max_values() {
+        assert_eq!(median_score(&[u64::MAX, u64::MAX]), u64::MAX);
+    }
+
+    #[test]
+    fn push_revision_wraps_around_ring_buffer() {
+        let mut account = StorageAccount::default();
+        for i in 0..(MAX_SCORE_HISTORY as u64 + 1) {
+            account.push_revision(i, i, Pubkey::default());
+        }
+
+        // The oldest entry (revision 0) should have been overwritten.
+        assert_eq!(account.revision_count, MAX_SCORE_HISTORY as u64 + 1);
+        assert_eq!(account.current_score().unwrap().score, MAX_SCORE_HISTORY as u64);
+    }
+
+    #[test]
+    fn current_score_is_none_before_any_revision() {
+        let account = StorageAccount::default();
+        assert!(account.current_score().is_none());
+    }
+}